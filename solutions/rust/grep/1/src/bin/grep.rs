@@ -0,0 +1,39 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use grep::{grep_from_entries, Flags, StringListEntry};
+
+/// Command-line front-end for the `grep` library: search for PATTERN in each
+/// FILE and print the matching lines to stdout. PATTERN and any FILE may be
+/// written as `@path` to read patterns (or, for FILE, more paths) from `path`,
+/// one per line, instead of passing them directly.
+#[derive(Debug, Parser)]
+#[command(name = "grep", about = "Search for PATTERN in each FILE.")]
+struct Cli {
+    /// Pattern to search for, or `@path` to read patterns from `path`.
+    pattern: String,
+    /// Files to search, or `@path` to read more file paths from `path`.
+    #[arg(required = true)]
+    files: Vec<String>,
+    #[command(flatten)]
+    flags: Flags,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let patterns = [StringListEntry::parse(&cli.pattern)];
+    let files: Vec<StringListEntry> = cli.files.iter().map(|f| StringListEntry::parse(f)).collect();
+    match grep_from_entries(&patterns, &cli.flags, &files) {
+        Ok(matches) if matches.is_empty() => ExitCode::FAILURE,
+        Ok(matches) => {
+            for line in matches {
+                println!("{line}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("grep: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
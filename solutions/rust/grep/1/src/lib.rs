@@ -1,40 +1,40 @@
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
 
 use anyhow::Error;
+use clap::Parser;
 
-/// While using `&[&str]` to handle flags is convenient for exercise purposes,
-/// and resembles the output of [`std::env::args`], in real-world projects it is
-/// both more convenient and more idiomatic to contain runtime configuration in
-/// a dedicated struct. Therefore, we suggest that you do so in this exercise.
-///
-/// [`std::env::args`]: https://doc.rust-lang.org/std/env/fn.args.html
-#[derive(Debug, Default)]
+/// Runtime configuration for `grep`, parsed by `clap`'s derive API instead of
+/// hand-matching flag strings, so an unrecognized flag becomes a regular
+/// [`clap::Error`] rather than a panic, and `--long` spellings come for free.
+#[derive(Debug, Default, Parser)]
 pub struct Flags {
+    #[arg(short = 'n', long = "line-number")]
     line_number: bool,
+    #[arg(short = 'l', long = "files-with-matches")]
     files_with_matches: bool,
+    #[arg(short = 'i', long = "ignore-case")]
     ignore_case: bool,
+    #[arg(short = 'v', long = "invert-match")]
     invert_match: bool,
+    #[arg(short = 'x', long = "line-regexp")]
     line_match: bool,
 }
 
 impl Flags {
+    /// Parses CLI-style flag tokens such as `&["-n", "-i"]`, panicking if one
+    /// of them isn't recognized.
     pub fn new(flags: &[&str]) -> Self {
-        let mut instance = Self {
-            ..Default::default()
-        };
-        for &flag in flags {
-            match flag {
-                "-n" => instance.line_number = true,
-                "-l" => instance.files_with_matches = true,
-                "-i" => instance.ignore_case = true,
-                "-v" => instance.invert_match = true,
-                "-x" => instance.line_match = true,
-                &_ => panic!("Unsupported flag {flag}"),
-            }
-        }
-        instance
+        Self::try_new(flags).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`Flags::new`], but returns a typed error instead of panicking
+    /// when one of the flags isn't recognized.
+    pub fn try_new(flags: &[&str]) -> Result<Self, clap::Error> {
+        Self::try_parse_from(std::iter::once("grep").chain(flags.iter().copied()))
     }
 }
 
@@ -65,55 +65,343 @@ fn format_result(
     format!("{file_path_prefix}{line_number_infix}{line}")
 }
 
+/// Reads a sequence of files as one continuous stream: each path is opened lazily
+/// (only once the previous file has been read to EOF, and the first only on the
+/// first read), so a caller never pays the cost of opening files it never reaches.
+///
+/// Because several source files are flattened into a single `Read`/`BufRead`
+/// stream, [`ConcatReader::current_path`] lets a caller recover which path the
+/// most recently read bytes came from.
+pub struct ConcatReader<P, I>
+where
+    P: AsRef<Path>,
+    I: Iterator<Item = P>,
+{
+    paths: I,
+    current: Option<(P, BufReader<File>)>,
+    // Bumped every time `advance` opens a new path. Since paths can repeat
+    // (the same file listed twice), this is what a caller should key off of
+    // to detect a new file, not equality of the path itself.
+    generation: u64,
+}
+
+impl<P, I> ConcatReader<P, I>
+where
+    P: AsRef<Path>,
+    I: Iterator<Item = P>,
+{
+    pub fn new(paths: I) -> Self {
+        Self {
+            paths,
+            current: None,
+            generation: 0,
+        }
+    }
+
+    /// The path of the file currently open, i.e. the one the most recently read
+    /// bytes came from. Never opens a file itself: returns `None` before the
+    /// first read, after [`ConcatReader::skip_current`], and once every path has
+    /// been exhausted.
+    pub fn current_path(&self) -> Option<&P> {
+        self.current.as_ref().map(|(path, _)| path)
+    }
+
+    /// Identifies which file is currently open: it changes every time `advance`
+    /// opens a new path, even if that path is equal to the previous one, so a
+    /// caller can detect a file boundary without relying on path equality.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Abandons whatever is left of the file currently being read, so the next
+    /// read resumes from the following path. Lets a caller that only needs one
+    /// hit per file (e.g. `files_with_matches`) skip the rest of it unread.
+    pub fn skip_current(&mut self) {
+        self.current = None;
+    }
+
+    /// Opens the next path in line, if any. Returns `Ok(false)` once `paths` is exhausted.
+    fn advance(&mut self) -> std::io::Result<bool> {
+        match self.paths.next() {
+            Some(path) => {
+                let file = File::open(path.as_ref())?;
+                self.current = Some((path, BufReader::new(file)));
+                self.generation += 1;
+                Ok(true)
+            }
+            None => {
+                self.current = None;
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl<P, I> Read for ConcatReader<P, I>
+where
+    P: AsRef<Path>,
+    I: Iterator<Item = P>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.current.is_none() && !self.advance()? {
+                return Ok(0);
+            }
+            let (_, reader) = self.current.as_mut().unwrap();
+            let read = reader.read(buf)?;
+            if read == 0 {
+                self.current = None;
+                continue;
+            }
+            return Ok(read);
+        }
+    }
+}
+
+impl<P, I> BufRead for ConcatReader<P, I>
+where
+    P: AsRef<Path>,
+    I: Iterator<Item = P>,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        loop {
+            if self.current.is_none() && !self.advance()? {
+                return Ok(&[]);
+            }
+            let (_, reader) = self.current.as_mut().unwrap();
+            if reader.fill_buf()?.is_empty() {
+                self.current = None;
+                continue;
+            }
+            break;
+        }
+        self.current.as_mut().unwrap().1.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Some((_, reader)) = self.current.as_mut() {
+            reader.consume(amt);
+        }
+    }
+}
+
+fn normalize_case(value: &str, ignore_case: bool) -> String {
+    if ignore_case {
+        value.to_lowercase()
+    } else {
+        value.to_string()
+    }
+}
+
 pub fn grep(pattern: &str, flags: &Flags, files: &[&str]) -> Result<Vec<String>, Error> {
+    grep_iter(pattern, flags, files).collect()
+}
+
+/// Same matching logic as [`grep`], but yields results lazily as lines are read
+/// instead of buffering the whole output in a `Vec`, so a caller can start
+/// consuming matches from a huge file before the rest of it has even been opened.
+pub fn grep_iter<'a>(
+    pattern: &'a str,
+    flags: &'a Flags,
+    files: &'a [&'a str],
+) -> impl Iterator<Item = Result<String, Error>> + 'a {
+    grep_multi_iter(vec![pattern.to_string()], flags, files)
+}
+
+/// Same as [`grep`], but matches a line against any one of several patterns
+/// instead of a single one, so a pattern set loaded from a file counts as a hit
+/// the moment any of its lines matches.
+pub fn grep_multi(patterns: Vec<String>, flags: &Flags, files: &[&str]) -> Result<Vec<String>, Error> {
+    grep_multi_iter(patterns, flags, files).collect()
+}
+
+/// Lazy, multi-pattern counterpart to [`grep_iter`] that [`grep`] and [`grep_multi`]
+/// both collect from.
+pub fn grep_multi_iter<'a>(
+    patterns: Vec<String>,
+    flags: &'a Flags,
+    files: &'a [&'a str],
+) -> impl Iterator<Item = Result<String, Error>> + 'a {
     let multiple_files = files.len() > 1;
-    let mut results = Vec::new();
-    let pattern = {
-        if flags.ignore_case {
-            pattern.to_lowercase()
-        } else {
-            pattern.to_string()
-        }
-    };
-    for &file_path in files {
-        let file = File::open(file_path)?;
-        let mut reader = BufReader::new(file);
-        let mut buf = String::new();
-        let mut line_number = 1usize;
-        while let Ok(length) = reader.read_line(&mut buf)
-            && length != 0
-        {
-            let line = buf.trim_end().to_string();
-            let candidate = {
-                if flags.ignore_case {
-                    line.to_lowercase()
-                } else {
-                    line.to_string()
-                }
+    let patterns: Vec<String> = patterns
+        .into_iter()
+        .map(|pattern| normalize_case(&pattern, flags.ignore_case))
+        .collect();
+    let mut reader = ConcatReader::new(files.iter().copied());
+    // Line numbering and `files_with_matches` both reset whenever `generation`
+    // changes, which is how the per-file `multiple_files` bookkeeping collapses
+    // into this single uniform loop over the concatenated stream. We key off
+    // `generation` rather than the path itself, since the same path can appear
+    // more than once in `files`.
+    let mut last_generation = 0u64;
+    let mut line_number = 0usize;
+    let mut already_matched = false;
+    std::iter::from_fn(move || {
+        loop {
+            let mut buf = String::new();
+            let length = match reader.read_line(&mut buf) {
+                Ok(length) => length,
+                Err(err) => return Some(Err(err.into())),
             };
-            let is_match = {
+            if length == 0 {
+                return None;
+            }
+            let file_path = *reader
+                .current_path()
+                .expect("a file is open after a successful read");
+            if reader.generation() != last_generation {
+                last_generation = reader.generation();
+                line_number = 0;
+                already_matched = false;
+            }
+            line_number += 1;
+            if already_matched {
+                continue;
+            }
+            let line = buf.trim_end().to_string();
+            let candidate = normalize_case(&line, flags.ignore_case);
+            let is_match = patterns.iter().any(|pattern| {
                 if flags.line_match {
-                    candidate == pattern
+                    candidate == *pattern
                 } else {
-                    candidate.contains(&pattern)
+                    candidate.contains(pattern)
                 }
-            };
+            });
             if flags.invert_match ^ is_match {
-                results.push(format_result(
-                    file_path,
-                    line_number,
-                    &line,
-                    flags,
-                    multiple_files,
-                ));
+                let result =
+                    format_result(file_path, line_number, &line, flags, multiple_files);
                 if flags.files_with_matches {
-                    // We already matched this file path, no need to check remaining lines.
-                    break;
+                    // We already matched this file path, no need to read the remaining lines.
+                    already_matched = true;
+                    reader.skip_current();
                 }
+                return Some(Ok(result));
             }
-            buf.clear();
-            line_number += 1;
         }
+    })
+}
+
+/// One entry in a list of files or patterns passed on the command line: either
+/// a literal value, or (when the raw token starts with `@`) the path to a file
+/// whose lines should be expanded in its place. Resolution happens lazily in
+/// [`expand_entries`], so a missing `File` only errors once it's reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringListEntry {
+    Literal(String),
+    File(String),
+}
+
+impl StringListEntry {
+    /// Parses one raw CLI token: a leading `@` names a file to expand, anything
+    /// else passes through unchanged.
+    pub fn parse(raw: &str) -> Self {
+        match raw.strip_prefix('@') {
+            Some(path) => Self::File(path.to_string()),
+            None => Self::Literal(raw.to_string()),
+        }
+    }
+}
+
+/// Expands a sequence of [`StringListEntry`] into the flat sequence of strings
+/// it denotes: a `Literal` yields itself, a `File` yields one item per line of
+/// that file, opened only once the entry is reached.
+pub fn expand_entries<'a, I>(entries: I) -> impl Iterator<Item = Result<String, Error>> + 'a
+where
+    I: Iterator<Item = &'a StringListEntry> + 'a,
+{
+    let mut entries = entries;
+    let mut current_file_lines: Option<std::io::Lines<BufReader<File>>> = None;
+    std::iter::from_fn(move || {
+        loop {
+            if let Some(lines) = &mut current_file_lines {
+                match lines.next() {
+                    Some(Ok(line)) => return Some(Ok(line)),
+                    Some(Err(err)) => return Some(Err(err.into())),
+                    None => current_file_lines = None,
+                }
+            }
+            match entries.next()? {
+                StringListEntry::Literal(value) => return Some(Ok(value.clone())),
+                StringListEntry::File(path) => match File::open(path) {
+                    Ok(file) => current_file_lines = Some(BufReader::new(file).lines()),
+                    Err(err) => return Some(Err(err.into())),
+                },
+            }
+        }
+    })
+}
+
+/// Runs [`grep_multi`] after expanding both the pattern list and the file list
+/// through [`expand_entries`], so either (or both) can be `@file` references.
+pub fn grep_from_entries(
+    patterns: &[StringListEntry],
+    flags: &Flags,
+    files: &[StringListEntry],
+) -> Result<Vec<String>, Error> {
+    let patterns = expand_entries(patterns.iter()).collect::<Result<Vec<_>, _>>()?;
+    let files = expand_entries(files.iter()).collect::<Result<Vec<_>, _>>()?;
+    let file_refs: Vec<&str> = files.iter().map(String::as_str).collect();
+    grep_multi(patterns, flags, &file_refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates (and returns the path of) a fixture file under a scratch
+    /// directory scoped to `name`, so concurrently-run tests don't collide.
+    fn write_fixture(name: &str, file_name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("grep_lib_test_{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file_name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn grep_iter_matches_across_multiple_files() {
+        let a = write_fixture("grep_iter_multi", "a.txt", "hello\nworld\n");
+        let b = write_fixture("grep_iter_multi", "b.txt", "foo\nhello\n");
+        let flags = Flags::default();
+        let files = [a.as_str(), b.as_str()];
+        let results: Vec<String> = grep_iter("hello", &flags, &files)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results, vec![format!("{a}:hello"), format!("{b}:hello")]);
+    }
+
+    #[test]
+    fn duplicate_file_paths_reset_line_numbers_via_generation() {
+        let a = write_fixture("duplicate_paths", "a.txt", "hello world\nfoo\n");
+        let flags = Flags::new(&["-n"]);
+        let files = [a.as_str(), a.as_str()];
+        let results = grep("hello", &flags, &files).unwrap();
+        assert_eq!(
+            results,
+            vec![format!("{a}:1:hello world"), format!("{a}:1:hello world")]
+        );
+    }
+
+    #[test]
+    fn files_with_matches_short_circuits_each_file() {
+        let a = write_fixture("files_with_matches", "a.txt", "hello\nhello\nhello\n");
+        let b = write_fixture("files_with_matches", "b.txt", "nope\nhello\n");
+        let flags = Flags::new(&["-l"]);
+        let files = [a.as_str(), b.as_str()];
+        let results = grep("hello", &flags, &files).unwrap();
+        assert_eq!(results, vec![a, b]);
+    }
+
+    #[test]
+    fn expand_entries_errors_only_once_a_missing_file_is_reached() {
+        let present = write_fixture("missing_file_lazy", "present.txt", "a\nb\n");
+        let missing = format!("{present}.does-not-exist");
+        let entries = [
+            StringListEntry::Literal(present.clone()),
+            StringListEntry::File(missing),
+        ];
+        let mut expanded = expand_entries(entries.iter());
+        assert_eq!(expanded.next().unwrap().unwrap(), present);
+        assert!(expanded.next().unwrap().is_err());
     }
-    Ok(results)
 }
@@ -1,3 +1,5 @@
+use std::io::BufRead;
+
 /// Yields each item of a and then each item of b
 pub fn append<I, J>(mut a: I, mut b: J) -> impl Iterator<Item = I::Item>
 where
@@ -49,6 +51,38 @@ where
     })
 }
 
+/// Applies `f` to each item of `iter`, then flattens the resulting iterators
+/// into one, equivalent to `concat(map(iter, f))` but without building the
+/// intermediate iterator of iterators.
+pub fn flat_map<I, F, U>(mut iter: I, f: F) -> impl Iterator<Item = U::Item>
+where
+    I: Iterator,
+    F: Fn(I::Item) -> U,
+    U: Iterator,
+{
+    let mut inner_iterator: Option<U> = None;
+    std::iter::from_fn(move || {
+        loop {
+            if let Some(inner) = &mut inner_iterator
+                && let Some(item) = inner.next()
+            {
+                return Some(item);
+            }
+            inner_iterator = Some(f(iter.next()?));
+        }
+    })
+}
+
+/// Flattens a `BufRead`'s lines into a single stream of `char`s, built on
+/// [`flat_map`]. Each line is collected into a `Vec<char>` first, since
+/// `str::chars` borrows from the line and couldn't otherwise outlive the
+/// closure that produced it.
+pub fn chars_of_lines<R: BufRead>(reader: R) -> impl Iterator<Item = char> {
+    flat_map(reader.lines().map_while(Result::ok), |line: String| {
+        line.chars().collect::<Vec<char>>().into_iter()
+    })
+}
+
 pub fn length<I: Iterator>(mut iter: I) -> usize {
     let mut length = 0;
     while iter.next().is_some() {
@@ -94,3 +128,142 @@ where
 pub fn reverse<I: DoubleEndedIterator>(mut iter: I) -> impl Iterator<Item = I::Item> {
     std::iter::from_fn(move || iter.next_back())
 }
+
+/// Groups `iter` into fixed-size `N`-item arrays, dropping a final chunk
+/// that has fewer than `N` items left in it.
+pub fn array_chunks<const N: usize, I>(mut iter: I) -> impl Iterator<Item = [I::Item; N]>
+where
+    I: Iterator,
+{
+    std::iter::from_fn(move || {
+        let mut chunk = Vec::with_capacity(N);
+        for _ in 0..N {
+            chunk.push(iter.next()?);
+        }
+        chunk.try_into().ok()
+    })
+}
+
+/// Yields the items of `iter` with a clone of `sep` inserted between every
+/// consecutive pair, with no separator before the first or after the last item.
+pub fn intersperse<I>(mut iter: I, sep: I::Item) -> impl Iterator<Item = I::Item>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    let mut next_item = iter.next();
+    let mut pending_sep = false;
+    std::iter::from_fn(move || {
+        if pending_sep {
+            pending_sep = false;
+            return Some(sep.clone());
+        }
+        let item = next_item.take()?;
+        next_item = iter.next();
+        pending_sep = next_item.is_some();
+        Some(item)
+    })
+}
+
+/// Yields items of `iter` up to, but not including, the first one for which
+/// `predicate` returns `false`.
+pub fn take_while<I, F>(mut iter: I, predicate: F) -> impl Iterator<Item = I::Item>
+where
+    I: Iterator,
+    F: Fn(&I::Item) -> bool,
+{
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        match iter.next() {
+            Some(item) if predicate(&item) => Some(item),
+            _ => {
+                done = true;
+                None
+            }
+        }
+    })
+}
+
+/// Yields `f(item)` for each item of `iter`, stopping at the first item for
+/// which `f` returns `None`.
+pub fn map_while<I, F, U>(mut iter: I, f: F) -> impl Iterator<Item = U>
+where
+    I: Iterator,
+    F: Fn(I::Item) -> Option<U>,
+{
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        match iter.next().and_then(&f) {
+            Some(item) => Some(item),
+            None => {
+                done = true;
+                None
+            }
+        }
+    })
+}
+
+/// Pairs up items from `a` and `b`, ending as soon as either iterator is exhausted.
+pub fn zip<I, J>(mut a: I, mut b: J) -> impl Iterator<Item = (I::Item, J::Item)>
+where
+    I: Iterator,
+    J: Iterator,
+{
+    std::iter::from_fn(move || Some((a.next()?, b.next()?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_chunks_drops_the_partial_last_chunk() {
+        let chunks: Vec<[i32; 3]> = array_chunks(vec![1, 2, 3, 4, 5, 6, 7].into_iter()).collect();
+        assert_eq!(chunks, vec![[1, 2, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn array_chunks_on_empty_input_yields_nothing() {
+        let chunks: Vec<[i32; 3]> = array_chunks(Vec::new().into_iter()).collect();
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn intersperse_never_trails_a_separator() {
+        let items: Vec<i32> = intersperse(vec![1, 2, 3].into_iter(), 0).collect();
+        assert_eq!(items, vec![1, 0, 2, 0, 3]);
+    }
+
+    #[test]
+    fn intersperse_on_empty_input_yields_nothing() {
+        let items: Vec<i32> = intersperse(Vec::new().into_iter(), 0).collect();
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn take_while_stops_at_the_first_failing_item() {
+        let items: Vec<i32> = take_while(vec![1, 2, 3, 4, 1].into_iter(), |&x| x < 3).collect();
+        assert_eq!(items, vec![1, 2]);
+    }
+
+    #[test]
+    fn map_while_stops_at_the_first_none() {
+        let items: Vec<i32> = map_while(vec![1, 2, 0, 3].into_iter(), |x| {
+            if x != 0 { Some(x * 2) } else { None }
+        })
+        .collect();
+        assert_eq!(items, vec![2, 4]);
+    }
+
+    #[test]
+    fn zip_ends_with_the_shorter_iterator() {
+        let pairs: Vec<(i32, char)> = zip(vec![1, 2, 3].into_iter(), vec!['a', 'b'].into_iter()).collect();
+        assert_eq!(pairs, vec![(1, 'a'), (2, 'b')]);
+    }
+}